@@ -0,0 +1,323 @@
+//! Discovers games installed through external launchers, as an alternative
+//! to scanning a directory with [`Game::from_path`].
+//!
+//! Each external launcher keeps its own manifest of installed games; the
+//! functions here read those manifests and turn them into [`Game`] values
+//! with `directory`, `launchers` and `platform` already filled in.
+
+#[cfg(feature = "discovery")]
+use crate::game::{Game, Platform, Runtime};
+#[cfg(feature = "discovery")]
+use std::io;
+#[cfg(feature = "discovery")]
+use std::path::{Path, PathBuf};
+
+/// Where a [`Game`] was discovered from.
+#[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Source {
+    /// Read from Legendary's `installed.json`.
+    Legendary,
+    /// Read from a Lutris game config in its `games` subdirectory.
+    Lutris,
+    /// Read from Steam's `libraryfolders.vdf` and `appmanifest_*.acf` files.
+    Steam,
+    /// Built by scanning a directory with [`Game::from_path`].
+    DirectoryScan,
+}
+
+/// Reads Legendary's `installed.json` (usually at
+/// `~/.config/legendary/installed.json`) and returns a [`Game`] per entry.
+#[cfg(feature = "discovery")]
+pub fn legendary_games(installed_json: &Path) -> io::Result<Vec<Game>> {
+    #[derive(serde::Deserialize)]
+    struct Entry {
+        install_path: PathBuf,
+        executable: PathBuf,
+        title: String,
+    }
+
+    let contents = std::fs::read_to_string(installed_json)?;
+    let entries: std::collections::HashMap<String, Entry> = serde_json::from_str(&contents)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+    Ok(entries
+        .into_values()
+        .map(|entry| {
+            let launcher = entry.install_path.join(entry.executable);
+            // Classify the executable the same way a directory scan would,
+            // rather than assuming every Legendary game is native: most of
+            // Legendary's catalogue (Epic Games Store titles) is Windows-only.
+            let (platform, runtime) = match Game::platform(&launcher) {
+                Some((platform, runtime)) => (Some(platform), runtime),
+                None => (None, None),
+            };
+
+            Game {
+                name: Some(entry.title),
+                platform,
+                runtime,
+                launchers: vec![launcher],
+                genres: vec![],
+                source: Source::Legendary,
+                directory: entry.install_path,
+            }
+        })
+        .collect())
+}
+
+/// Reads Lutris' per-game YAML configs out of its `games` subdirectory
+/// (usually `~/.config/lutris/games`) and returns a [`Game`] per `.yml`
+/// file that has an `exe` key.
+///
+/// Only the `exe` and `runner` keys are used; Lutris' configs carry far more
+/// than we need, so we scan for those lines rather than pulling in a YAML
+/// parser.
+#[cfg(feature = "discovery")]
+pub fn lutris_games(games_dir: &Path) -> io::Result<Vec<Game>> {
+    let mut games = vec![];
+
+    for entry in std::fs::read_dir(games_dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("yml") {
+            continue;
+        }
+
+        let contents = std::fs::read_to_string(&path)?;
+        let exe = contents
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("exe:"))
+            .map(|value| PathBuf::from(value.trim().trim_matches('"')));
+
+        let exe = match exe {
+            Some(exe) => exe,
+            None => continue,
+        };
+        let directory = exe.parent().map_or_else(|| PathBuf::from("."), PathBuf::from);
+
+        // Lutris' own config says which runner it launches the game with;
+        // no need to guess like a bare directory scan would. Runners besides
+        // `linux`/`wine` (dosbox, scummvm, wine-ge-*, ...) fall back to
+        // classifying the executable itself, the same way Legendary/Steam do,
+        // rather than assuming they're native.
+        let runner = contents
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("runner:"))
+            .map(|value| value.trim().trim_matches('"').to_string());
+        let (platform, runtime) = match runner.as_deref() {
+            Some("linux") => (Some(Platform::Native), None),
+            Some("wine") => (Some(Platform::Wine), None),
+            _ => match Game::platform(&exe) {
+                Some((platform, runtime)) => (Some(platform), runtime),
+                None => (None, None),
+            },
+        };
+
+        games.push(Game {
+            name: Game::basename(&path).map(|f| f.trim_end_matches(".yml").to_string()),
+            platform,
+            runtime,
+            launchers: vec![exe],
+            genres: vec![],
+            source: Source::Lutris,
+            directory,
+        });
+    }
+
+    Ok(games)
+}
+
+/// Reads Steam's `libraryfolders.vdf` to find every Steam library, then
+/// every `appmanifest_*.acf` inside each library's `steamapps` for the
+/// games installed there.
+#[cfg(feature = "discovery")]
+pub fn steam_games(steam_dir: &Path) -> io::Result<Vec<Game>> {
+    let default_steamapps = steam_dir.join("steamapps");
+    let mut libraries = vec![default_steamapps.clone()];
+
+    if let Ok(contents) = std::fs::read_to_string(default_steamapps.join("libraryfolders.vdf")) {
+        libraries.extend(
+            vdf_string_values(&contents, "path")
+                .into_iter()
+                .map(|path| PathBuf::from(path).join("steamapps")),
+        );
+    }
+
+    let mut games = vec![];
+    for steamapps in libraries {
+        let read_dir = match std::fs::read_dir(&steamapps) {
+            Ok(read_dir) => read_dir,
+            Err(_) => continue,
+        };
+
+        for entry in read_dir {
+            let path = entry?.path();
+            let is_manifest = path.file_name().and_then(|f| f.to_str()).map_or(false, |f| {
+                f.starts_with("appmanifest_") && f.ends_with(".acf")
+            });
+            if !is_manifest {
+                continue;
+            }
+
+            let contents = std::fs::read_to_string(&path)?;
+            let name = vdf_string_values(&contents, "name").into_iter().next();
+            let installdir = vdf_string_values(&contents, "installdir").into_iter().next();
+            let appid = path
+                .file_stem()
+                .and_then(|f| f.to_str())
+                .and_then(|f| f.strip_prefix("appmanifest_"));
+
+            if let Some(installdir) = installdir {
+                let directory = steamapps.join("common").join(installdir);
+                // Sniff the actual launchers the same way a directory scan
+                // would, rather than guessing the platform from
+                // `CompatToolMapping` alone: a game with no entry there is
+                // just as often native (no compat tool needed) as it is an
+                // unmapped Wine title.
+                let (mut platform, mut runtime, launchers) = Game::find_launchers(&directory);
+
+                // A `CompatToolMapping` entry means Steam is running this
+                // appid's Windows launcher through a specific Proton build.
+                if let (Some(Platform::Wine), Some(id)) = (&platform, appid) {
+                    if let Some(tool) = compat_tool(steam_dir, id) {
+                        platform = Some(Platform::Proton);
+                        runtime = Some(Runtime(tool));
+                    }
+                }
+
+                games.push(Game {
+                    name,
+                    platform,
+                    runtime,
+                    launchers,
+                    genres: vec![],
+                    source: Source::Steam,
+                    directory,
+                });
+            }
+        }
+    }
+
+    Ok(games)
+}
+
+/// Looks up which compatibility tool (e.g. `"proton_experimental"`) Steam
+/// picked for `appid` in `config/config.vdf`'s `CompatToolMapping` section.
+#[cfg(feature = "discovery")]
+fn compat_tool(steam_dir: &Path, appid: &str) -> Option<String> {
+    let contents = std::fs::read_to_string(steam_dir.join("config/config.vdf")).ok()?;
+    let marker = format!("\"{}\"", appid);
+    let start = contents.lines().position(|line| line.trim() == marker)?;
+
+    // The mapping entry is a handful of lines long
+    // (`"name"`, `"config"`, `"priority"`); no need for a real parser.
+    contents.lines().skip(start).take(6).find_map(|line| {
+        line.trim()
+            .strip_prefix("\"name\"")
+            .map(str::trim)
+            .and_then(|rest| rest.strip_prefix('"'))
+            .and_then(|rest| rest.strip_suffix('"'))
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+    })
+}
+
+/// Extracts the string values for a VDF key, e.g. the `"/mnt/data"` out of
+/// `"path"		"/mnt/data"`. Steam's VDF format is a small, flat,
+/// Valve-specific key/value syntax; we only need string values here, so a
+/// full parser is overkill.
+#[cfg(feature = "discovery")]
+fn vdf_string_values(contents: &str, key: &str) -> Vec<String> {
+    let needle = format!("\"{}\"", key);
+
+    contents
+        .lines()
+        .filter_map(|line| {
+            line.trim()
+                .strip_prefix(&needle)
+                .map(str::trim)
+                .and_then(|rest| rest.strip_prefix('"'))
+                .and_then(|rest| rest.strip_suffix('"'))
+                .map(String::from)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "discovery")]
+    #[test]
+    fn test_vdf_string_values() {
+        let contents = "\"libraryfolders\"\n{\n\t\"0\"\n\t{\n\t\t\"path\"\t\t\"/mnt/data\"\n\t}\n}";
+        assert_eq!(vec!["/mnt/data".to_string()], vdf_string_values(contents, "path"));
+    }
+
+    #[cfg(feature = "discovery")]
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rusteam-test-discovery-{}", name));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[cfg(feature = "discovery")]
+    #[test]
+    fn test_legendary_games_reads_installed_json() {
+        let dir = temp_dir("legendary");
+        let install_path = dir.join("SomeGame");
+        std::fs::create_dir_all(&install_path).unwrap();
+        std::fs::write(
+            dir.join("installed.json"),
+            format!(
+                r#"{{"SomeGame": {{"install_path": "{}", "executable": "SomeGame.exe", "title": "Some Game"}}}}"#,
+                install_path.display()
+            ),
+        )
+        .unwrap();
+
+        let games = legendary_games(&dir.join("installed.json")).unwrap();
+        assert_eq!(1, games.len());
+        assert_eq!(Some("Some Game".to_string()), games[0].name);
+        assert_eq!(Some(Platform::Wine), games[0].platform);
+        assert_eq!(Source::Legendary, games[0].source);
+    }
+
+    #[cfg(feature = "discovery")]
+    #[test]
+    fn test_lutris_games_reads_runner_from_yaml() {
+        let dir = temp_dir("lutris");
+        std::fs::write(
+            dir.join("some-game.yml"),
+            "game:\n  exe: /home/user/games/some-game/start.sh\nrunner: linux\n",
+        )
+        .unwrap();
+
+        let games = lutris_games(&dir).unwrap();
+        assert_eq!(1, games.len());
+        assert_eq!(Some(Platform::Native), games[0].platform);
+        assert_eq!(Source::Lutris, games[0].source);
+    }
+
+    #[cfg(feature = "discovery")]
+    #[test]
+    fn test_steam_games_reads_appmanifest_and_launchers() {
+        let dir = temp_dir("steam");
+        let steamapps = dir.join("steamapps");
+        let common = steamapps.join("common").join("SomeGame");
+        std::fs::create_dir_all(&common).unwrap();
+        std::fs::write(common.join("somegame.sh"), "#!/bin/sh\n").unwrap();
+
+        std::fs::write(
+            steamapps.join("appmanifest_123.acf"),
+            "\"AppState\"\n{\n\t\"appid\"\t\t\"123\"\n\t\"name\"\t\t\"Some Game\"\n\t\"installdir\"\t\t\"SomeGame\"\n}",
+        )
+        .unwrap();
+
+        let games = steam_games(&dir).unwrap();
+        assert_eq!(1, games.len());
+        assert_eq!(Some("Some Game".to_string()), games[0].name);
+        assert_eq!(Some(Platform::Native), games[0].platform);
+        assert_eq!(vec![common.join("somegame.sh")], games[0].launchers);
+        assert_eq!(Source::Steam, games[0].source);
+    }
+}
@@ -1,21 +1,89 @@
+use crate::discovery::Source;
 use crate::filesystem::{entries, has_same_name_as_parent_dir};
+use crate::launch::{LaunchError, LaunchOptions, LauncherSelector};
 use std::fmt;
 use std::path::{Path, PathBuf};
+use std::process::Child;
+
+/// Name of the optional per-game metadata override file, read from the
+/// game's root `directory`.
+#[cfg(feature = "config")]
+const OVERRIDE_FILENAME: &str = ".rusteam.json";
 
 /// A platform that a [`Game`] can be developed for.
 #[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(rename_all = "snake_case"))]
 pub enum Platform {
     Native,
     Wine,
+    Proton,
 }
 
+/// Which exact build of a compatibility layer a [`Platform::Wine`] or
+/// [`Platform::Proton`] launcher should run under, e.g. `"GE-Proton8-25"`
+/// or a path to a specific Wine build. `None` means the platform is known
+/// but the specific build isn't.
+#[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(transparent))]
+pub struct Runtime(pub String);
+
 /// A genre that a [`Game`] can belong to.
 #[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(rename_all = "snake_case"))]
 pub enum Genre {
     Action,
     Platformer,
 }
 
+/// A per-game metadata override, read from [`OVERRIDE_FILENAME`] in the
+/// game's root directory. Every field is optional: only the keys present in
+/// the file are merged over the inferred [`Game`] data, everything else is
+/// left to inference.
+#[cfg(feature = "config")]
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+struct GameOverride {
+    name: Option<String>,
+    platform: Option<Platform>,
+    runtime: Option<Runtime>,
+    /// Paths relative to the game's `directory`.
+    launchers: Option<Vec<PathBuf>>,
+    genres: Option<Vec<Genre>>,
+}
+
+/// An error that can occur while loading a [`Game`]'s on-disk metadata
+/// override.
+#[cfg(feature = "config")]
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The override file exists but couldn't be read.
+    Read(std::io::Error),
+    /// The override file exists but isn't valid JSON for [`GameOverride`].
+    Parse(serde_json::Error),
+}
+
+#[cfg(feature = "config")]
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Read(err) => write!(f, "failed to read {}: {}", OVERRIDE_FILENAME, err),
+            Self::Parse(err) => write!(f, "failed to parse {}: {}", OVERRIDE_FILENAME, err),
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Read(err) => Some(err),
+            Self::Parse(err) => Some(err),
+        }
+    }
+}
+
 /// A game on your hard drive.
 #[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd)]
 pub struct Game {
@@ -30,6 +98,19 @@ pub struct Game {
     pub genres: Vec<Genre>,
     /// Paths to executable files that start the game.
     pub launchers: Vec<PathBuf>,
+    /// Which build of Wine or Proton the launchers run under, if known.
+    /// Only meaningful when `platform` is [`Platform::Wine`] or
+    /// [`Platform::Proton`].
+    pub runtime: Option<Runtime>,
+    /// Where this [`Game`] was discovered from.
+    pub source: Source,
+}
+
+/// Which code path [`Game::launch`] should take for a given [`Platform`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum LaunchStrategy {
+    Native,
+    Wine,
 }
 
 impl fmt::Display for Game {
@@ -47,7 +128,7 @@ impl Game {
     /// Most of the metadata about the game is inferred.
     /// Currently there is no way to customize the inferred data.
     pub fn from_path(directory: PathBuf) -> Self {
-        let (platform, launchers) = Self::find_launchers(&directory);
+        let (platform, runtime, launchers) = Self::find_launchers(&directory);
 
         Self {
             // Name of the game is the name of its directory.
@@ -55,12 +136,138 @@ impl Game {
             // Genres is beyond us for now.
             genres: vec![],
             platform,
+            runtime,
             launchers,
             directory,
+            source: Source::DirectoryScan,
         }
     }
 
-    fn find_launchers(directory: &Path) -> (Option<Platform>, Vec<PathBuf>) {
+    /// Constructs a [`Game`] the same way as [`Self::from_path`], but also
+    /// reads an optional [`OVERRIDE_FILENAME`] from the game's root
+    /// directory and merges it over the inferred fields.
+    ///
+    /// Inferred values only fill in the keys the override file omits. Parse
+    /// errors in the override file are surfaced rather than silently falling
+    /// back to inference, so a broken override is debuggable instead of
+    /// looking like "no override".
+    #[cfg(feature = "config")]
+    pub fn from_path_with_overrides(directory: PathBuf) -> Result<Self, ConfigError> {
+        let game = Self::from_path(directory);
+
+        match Self::read_override(&game.directory)? {
+            Some(over) => Ok(game.with_override(over)),
+            None => Ok(game),
+        }
+    }
+
+    #[cfg(feature = "config")]
+    fn read_override(directory: &Path) -> Result<Option<GameOverride>, ConfigError> {
+        let path = directory.join(OVERRIDE_FILENAME);
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = std::fs::read_to_string(&path).map_err(ConfigError::Read)?;
+        let over = serde_json::from_str(&contents).map_err(ConfigError::Parse)?;
+
+        Ok(Some(over))
+    }
+
+    #[cfg(feature = "config")]
+    fn with_override(mut self, over: GameOverride) -> Self {
+        if let Some(name) = over.name {
+            self.name = Some(name);
+        }
+        if let Some(platform) = over.platform {
+            self.platform = Some(platform);
+        }
+        if let Some(runtime) = over.runtime {
+            self.runtime = Some(runtime);
+        }
+        if let Some(launchers) = over.launchers {
+            self.launchers = launchers
+                .into_iter()
+                .map(|launcher| self.directory.join(launcher))
+                .collect();
+        }
+        if let Some(genres) = over.genres {
+            self.genres = genres;
+        }
+
+        self
+    }
+
+    /// Starts the game: spawns the chosen launcher directly for
+    /// [`Platform::Native`], or runs it through the compat layer configured
+    /// in `opts` (installing DXVK into the prefix before the first launch,
+    /// if configured) for [`Platform::Wine`] and [`Platform::Proton`] —
+    /// Proton is, after all, just a particular Wine build, so it goes
+    /// through the same `wine_binary`/`prefix` machinery.
+    ///
+    /// `opts` is only consulted for [`Platform::Wine`] and
+    /// [`Platform::Proton`] games; pass `None` for native ones.
+    pub fn launch(
+        &self,
+        selector: LauncherSelector,
+        opts: Option<&LaunchOptions>,
+    ) -> Result<Child, LaunchError> {
+        let launcher = self
+            .pick_launcher(&selector)
+            .ok_or(LaunchError::LauncherNotFound)?;
+
+        match Self::launch_strategy(&self.platform) {
+            LaunchStrategy::Wine => Self::launch_wine(launcher, opts),
+            LaunchStrategy::Native => Self::launch_native(launcher, &self.directory),
+        }
+    }
+
+    /// Picks how to start a launcher for the given `platform`. Kept as its
+    /// own pure function (rather than inlined in [`Self::launch`]'s match)
+    /// so the routing can be tested directly. The match has no wildcard arm,
+    /// so a new [`Platform`] variant fails to compile until it is routed here.
+    fn launch_strategy(platform: &Option<Platform>) -> LaunchStrategy {
+        match platform {
+            Some(Platform::Wine) | Some(Platform::Proton) => LaunchStrategy::Wine,
+            Some(Platform::Native) | None => LaunchStrategy::Native,
+        }
+    }
+
+    fn pick_launcher(&self, selector: &LauncherSelector) -> Option<&PathBuf> {
+        match selector {
+            LauncherSelector::Index(index) => self.launchers.get(*index),
+            LauncherSelector::Name(name) => self.launchers.iter().find(|launcher| {
+                launcher.file_name().and_then(|f| f.to_str()) == Some(name.as_str())
+            }),
+        }
+    }
+
+    fn launch_native(launcher: &Path, directory: &Path) -> Result<Child, LaunchError> {
+        std::process::Command::new(launcher)
+            .current_dir(directory)
+            .spawn()
+            .map_err(LaunchError::Spawn)
+    }
+
+    #[cfg(feature = "wine")]
+    fn launch_wine(launcher: &Path, opts: Option<&LaunchOptions>) -> Result<Child, LaunchError> {
+        let opts = opts.ok_or(LaunchError::MissingLaunchOptions)?;
+        opts.ensure_prefix()?;
+
+        std::process::Command::new(&opts.wine_binary)
+            .arg(launcher)
+            .env("WINEPREFIX", &opts.prefix)
+            .spawn()
+            .map_err(LaunchError::Spawn)
+    }
+
+    #[cfg(not(feature = "wine"))]
+    fn launch_wine(_launcher: &Path, _opts: Option<&LaunchOptions>) -> Result<Child, LaunchError> {
+        Err(LaunchError::WineFeatureDisabled)
+    }
+
+    pub(crate) fn find_launchers(directory: &Path) -> (Option<Platform>, Option<Runtime>, Vec<PathBuf>) {
         // We check for knows launchers in the root of the directory.
 
         let launchers = entries(directory)
@@ -68,28 +275,41 @@ impl Game {
             .filter(|filepath| Self::is_launcher(filepath))
             .collect::<Vec<PathBuf>>();
 
-        // We can tell the platform if all found launchers belong to it.
+        // We can tell the platform (and, if known, the runtime) if all
+        // found launchers belong to it.
 
-        (Self::same_platform(launchers.as_slice()), launchers)
+        let (platform, runtime) = Self::same_platform(launchers.as_slice());
+        (platform, runtime, launchers)
     }
 
-    fn same_platform(launchers: &[PathBuf]) -> Option<Platform> {
-        if launchers.is_empty() {
-            None
+    fn same_platform(launchers: &[PathBuf]) -> (Option<Platform>, Option<Runtime>) {
+        let Some((first_platform, first_runtime)) = launchers.first().and_then(Self::platform) else {
+            return (None, None);
+        };
+
+        let all_same_platform = launchers
+            .iter()
+            .all(|l| Self::platform(l).map_or(false, |(p, _)| p == first_platform));
+
+        if all_same_platform {
+            (Some(first_platform), first_runtime)
         } else {
-            Self::platform(&launchers[0]).filter(|first_platform| {
-                launchers
-                    .iter()
-                    .all(|l| Self::platform(l).filter(|p| p == first_platform).is_some())
-            })
+            (None, None)
         }
     }
 
-    fn platform(file: &Path) -> Option<Platform> {
+    /// Classifies a launcher's platform, along with its runtime build when
+    /// that's knowable from the file alone (it currently isn't: telling
+    /// Wine and Proton `.exe` launchers apart needs context we don't have
+    /// here, so content-based detection always reports `Platform::Wine`
+    /// with no runtime; callers such as Steam discovery can fill in a more
+    /// specific [`Platform::Proton`] and [`Runtime`] from external
+    /// metadata).
+    pub(crate) fn platform(file: &Path) -> Option<(Platform, Option<Runtime>)> {
         match file {
-            file if Self::is_native(file) => Some(Platform::Native),
-            file if Self::is_wine(file) => Some(Platform::Wine),
-            _ => None,
+            file if Self::is_native(file) => Some((Platform::Native, None)),
+            file if Self::is_wine(file) => Some((Platform::Wine, None)),
+            file => Self::platform_by_magic(file).map(|platform| (platform, None)),
         }
     }
 
@@ -97,6 +317,7 @@ impl Game {
         !Self::is_uninstall(filepath)
             && (Self::is_native(filepath)
                 || Self::is_wine(filepath)
+                || Self::platform_by_magic(filepath).is_some()
                 || has_same_name_as_parent_dir(filepath))
     }
 
@@ -107,16 +328,53 @@ impl Game {
             .map_or(false, |f| f.contains("uninstall"))
     }
 
-    /// Checks if file is a native Linux executable (empirically).
+    /// Checks if file is a native Linux executable (empirically), by
+    /// extension. This is a fast pre-filter; [`Self::platform_by_magic`]
+    /// catches extension-less native binaries.
     fn is_native(file: &Path) -> bool {
         Self::extension_in(file, &["sh", "x86", "x86_64"])
     }
 
-    /// Checks if file is a Wine executable (empirically).
+    /// Checks if file is a Wine executable (empirically), by extension.
     fn is_wine(file: &Path) -> bool {
         Self::extension_in(file, &["exe"])
     }
 
+    /// Sniffs the platform of a file whose extension didn't already match
+    /// [`Self::is_native`] or [`Self::is_wine`], by reading its magic bytes.
+    /// Requires the owner-executable permission bit so we don't pick up
+    /// arbitrary non-executable files that happen to share a magic number.
+    fn platform_by_magic(file: &Path) -> Option<Platform> {
+        if !Self::is_executable(file) {
+            return None;
+        }
+
+        match Self::magic_bytes(file)? {
+            [0x7F, b'E', b'L', b'F'] => Some(Platform::Native),
+            [b'M', b'Z', ..] => Some(Platform::Wine),
+            _ => None,
+        }
+    }
+
+    /// Reads the first 4 bytes of a file, e.g. to check for an ELF or DOS
+    /// (`MZ`) header.
+    fn magic_bytes(file: &Path) -> Option<[u8; 4]> {
+        use std::io::Read;
+
+        let mut buf = [0u8; 4];
+        std::fs::File::open(file).ok()?.read_exact(&mut buf).ok()?;
+        Some(buf)
+    }
+
+    /// Checks if file has the owner-executable permission bit set.
+    fn is_executable(file: &Path) -> bool {
+        use std::os::unix::fs::PermissionsExt;
+
+        std::fs::metadata(file)
+            .map(|metadata| metadata.permissions().mode() & 0o100 != 0)
+            .unwrap_or(false)
+    }
+
     /// Checks if file has one of the extensions.
     fn extension_in(file: &Path, extensions: &[&str]) -> bool {
         file.extension()
@@ -125,7 +383,7 @@ impl Game {
     }
 
     /// Gets the basename out of a path.
-    fn basename(path: &Path) -> Option<String> {
+    pub(crate) fn basename(path: &Path) -> Option<String> {
         path.file_name().and_then(|f| f.to_str()).map(String::from)
     }
 }
@@ -149,6 +407,36 @@ mod tests {
         assert!(Game::is_wine(Path::new("win_game/launcher.exe")))
     }
 
+    fn write_temp_file(name: &str, contents: &[u8], executable: bool) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("rusteam-test-{}", name));
+        std::fs::write(&path, contents).unwrap();
+
+        let mode = if executable { 0o755 } else { 0o644 };
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn test_platform_by_magic_detects_elf() {
+        let path = write_temp_file("elf-binary", &[0x7F, b'E', b'L', b'F'], true);
+        assert_eq!(Some(Platform::Native), Game::platform_by_magic(&path));
+    }
+
+    #[test]
+    fn test_platform_by_magic_detects_dos_header() {
+        let path = write_temp_file("dos-binary", &[b'M', b'Z', 0x90, 0x00], true);
+        assert_eq!(Some(Platform::Wine), Game::platform_by_magic(&path));
+    }
+
+    #[test]
+    fn test_platform_by_magic_requires_executable_bit() {
+        let path = write_temp_file("non-exec-elf", &[0x7F, b'E', b'L', b'F'], false);
+        assert_eq!(None, Game::platform_by_magic(&path));
+    }
+
     #[test]
     fn test_extension_in() {
         assert!(Game::extension_in(
@@ -157,6 +445,88 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn test_same_platform_agrees_across_launchers() {
+        let launchers = vec![
+            PathBuf::from("game/run.sh"),
+            PathBuf::from("game/run.x86_64"),
+        ];
+        assert_eq!(
+            (Some(Platform::Native), None),
+            Game::same_platform(&launchers)
+        );
+    }
+
+    #[test]
+    fn test_same_platform_disagrees_across_launchers() {
+        let launchers = vec![PathBuf::from("game/run.sh"), PathBuf::from("game/run.exe")];
+        assert_eq!((None, None), Game::same_platform(&launchers));
+    }
+
+    fn test_game(launchers: Vec<PathBuf>) -> Game {
+        Game {
+            name: None,
+            platform: None,
+            runtime: None,
+            directory: PathBuf::from("/tmp/game"),
+            genres: vec![],
+            launchers,
+            source: Source::DirectoryScan,
+        }
+    }
+
+    #[test]
+    fn test_pick_launcher_by_index() {
+        let game = test_game(vec![PathBuf::from("a.sh"), PathBuf::from("b.sh")]);
+        assert_eq!(
+            Some(&PathBuf::from("b.sh")),
+            game.pick_launcher(&LauncherSelector::Index(1))
+        );
+    }
+
+    #[test]
+    fn test_pick_launcher_by_name() {
+        let game = test_game(vec![
+            PathBuf::from("game/a.sh"),
+            PathBuf::from("game/b.sh"),
+        ]);
+        assert_eq!(
+            Some(&PathBuf::from("game/b.sh")),
+            game.pick_launcher(&LauncherSelector::Name("b.sh".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_pick_launcher_not_found() {
+        let game = test_game(vec![PathBuf::from("a.sh")]);
+        assert_eq!(None, game.pick_launcher(&LauncherSelector::Index(5)));
+        assert_eq!(
+            None,
+            game.pick_launcher(&LauncherSelector::Name("missing.sh".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_launch_strategy_routes_wine_and_proton_through_compat_layer() {
+        assert_eq!(
+            LaunchStrategy::Wine,
+            Game::launch_strategy(&Some(Platform::Wine))
+        );
+        assert_eq!(
+            LaunchStrategy::Wine,
+            Game::launch_strategy(&Some(Platform::Proton))
+        );
+    }
+
+    #[test]
+    fn test_launch_strategy_routes_native_and_unknown_directly() {
+        assert_eq!(
+            LaunchStrategy::Native,
+            Game::launch_strategy(&Some(Platform::Native))
+        );
+        assert_eq!(LaunchStrategy::Native, Game::launch_strategy(&None));
+    }
+
     #[test]
     fn test_basename() {
         assert_eq!(
@@ -164,4 +534,45 @@ mod tests {
             Game::basename(Path::new("/home/file.png"))
         )
     }
+
+    #[cfg(feature = "config")]
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rusteam-test-{}", name));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn test_read_override_missing_file_returns_none() {
+        let dir = temp_dir("no-override");
+        assert!(Game::read_override(&dir).unwrap().is_none());
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn test_read_override_merges_over_inferred_fields() {
+        let dir = temp_dir("merge-override");
+        std::fs::write(
+            dir.join(OVERRIDE_FILENAME),
+            r#"{"name": "Override Name", "platform": "wine"}"#,
+        )
+        .unwrap();
+
+        let game = Game::from_path_with_overrides(dir).unwrap();
+        assert_eq!(Some("Override Name".to_string()), game.name);
+        assert_eq!(Some(Platform::Wine), game.platform);
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn test_read_override_surfaces_parse_errors() {
+        let dir = temp_dir("bad-override");
+        std::fs::write(dir.join(OVERRIDE_FILENAME), "not json").unwrap();
+
+        assert!(matches!(
+            Game::read_override(&dir),
+            Err(ConfigError::Parse(_))
+        ));
+    }
 }
@@ -0,0 +1,104 @@
+//! Starting a [`Game`](crate::game::Game): spawning its launcher directly
+//! for [`Platform::Native`](crate::game::Platform::Native), or running it
+//! through Wine for [`Platform::Wine`](crate::game::Platform::Wine).
+
+use std::path::{Path, PathBuf};
+
+/// Picks which of a [`Game`](crate::game::Game)'s `launchers` to start,
+/// when there's more than one.
+#[derive(Debug, Clone)]
+pub enum LauncherSelector {
+    /// The launcher at this position in `Game::launchers`.
+    Index(usize),
+    /// The launcher whose file name matches exactly.
+    Name(String),
+}
+
+/// Wine/DXVK configuration needed to run a [`Platform::Wine`](crate::game::Platform::Wine)
+/// game. Unused for [`Platform::Native`](crate::game::Platform::Native) ones.
+#[cfg(feature = "wine")]
+#[derive(Debug, Clone)]
+pub struct LaunchOptions {
+    /// Path to the Wine build to run the game with.
+    pub wine_binary: PathBuf,
+    /// Path to the per-game Wine prefix. Created on first launch if it
+    /// doesn't exist yet.
+    pub prefix: PathBuf,
+    /// DXVK to install into `prefix` before the first launch, if any. The
+    /// string is passed to `Dxvk::install` as the path to an unpacked DXVK
+    /// build; a bare version number is not looked up or downloaded.
+    pub dxvk_version: Option<String>,
+}
+
+/// Without the `wine` feature there's nothing to configure; this uninhabited
+/// stand-in keeps [`Game::launch`](crate::game::Game::launch)'s signature
+/// the same regardless of which features are enabled.
+#[cfg(not(feature = "wine"))]
+#[derive(Debug, Clone)]
+pub struct LaunchOptions(std::convert::Infallible);
+
+#[cfg(feature = "wine")]
+impl LaunchOptions {
+    /// Makes sure `prefix` exists and has DXVK installed, mirroring how the
+    /// anime launcher SDKs wrap `wincompatlib` to apply Wine/DXVK to a
+    /// prefix. A no-op on every launch after the first.
+    pub(crate) fn ensure_prefix(&self) -> Result<(), LaunchError> {
+        use wincompatlib::prelude::*;
+
+        if self.prefix.exists() {
+            return Ok(());
+        }
+
+        let wine = Wine::from_binary(&self.wine_binary).with_prefix(&self.prefix);
+        wine.update_prefix(None).map_err(LaunchError::PrefixSetup)?;
+
+        if let Some(version) = &self.dxvk_version {
+            Dxvk::install(&wine, Path::new(version), InstallParams::default())
+                .map_err(LaunchError::PrefixSetup)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An error that can occur while launching a [`Game`](crate::game::Game).
+#[derive(Debug)]
+pub enum LaunchError {
+    /// No launcher matched the given [`LauncherSelector`].
+    LauncherNotFound,
+    /// The game's `platform` is `Wine` or `Proton` but no [`LaunchOptions`]
+    /// were given.
+    #[cfg(feature = "wine")]
+    MissingLaunchOptions,
+    /// Setting up the Wine prefix or installing DXVK failed.
+    #[cfg(feature = "wine")]
+    PrefixSetup(wincompatlib::error::Error),
+    /// The game's `platform` is `Wine` or `Proton`, but rusteam was built
+    /// without the `wine` feature.
+    #[cfg(not(feature = "wine"))]
+    WineFeatureDisabled,
+    /// Spawning the launcher process failed.
+    Spawn(std::io::Error),
+}
+
+impl std::fmt::Display for LaunchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::LauncherNotFound => write!(f, "no matching launcher found"),
+            #[cfg(feature = "wine")]
+            Self::MissingLaunchOptions => {
+                write!(f, "this game needs Wine, but no LaunchOptions were given")
+            }
+            #[cfg(feature = "wine")]
+            Self::PrefixSetup(err) => write!(f, "failed to set up the Wine prefix: {}", err),
+            #[cfg(not(feature = "wine"))]
+            Self::WineFeatureDisabled => write!(
+                f,
+                "this game needs Wine, but rusteam was built without the `wine` feature"
+            ),
+            Self::Spawn(err) => write!(f, "failed to spawn the launcher: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for LaunchError {}